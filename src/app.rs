@@ -1,35 +1,75 @@
-use std::f32::INFINITY;
-use std::cmp::{Ord, Ordering};
-use std::{fmt, fs};
-use std::str::FromStr;
-use std::num::ParseIntError;
-use std::ops::Index;
-use std::collections::{BinaryHeap, HashSet, HashMap};
-use std::iter::FromIterator;
+use core::fmt;
+use core::str::FromStr;
+use core::num::ParseIntError;
+use core::ops::Index;
+use core::iter::FromIterator;
 
+#[cfg(feature = "std")]
+use std::fs;
+
+#[cfg(feature = "std")]
+use std::collections::{HashSet, HashMap};
+#[cfg(not(feature = "std"))]
+use hashbrown::{HashSet, HashMap};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
 use args::ArgsError;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Coord {
-    x: usize,
-    y: usize
+    values: Vec<usize>
 }
 
 impl Coord {
+    pub fn new(values: Vec<usize>) -> Coord {
+        Coord {values}
+    }
+
+    // 2D convenience constructor
+    pub fn xy(x: usize, y: usize) -> Coord {
+        Coord::new(vec![x, y])
+    }
+
+    pub fn dims(&self) -> usize {
+        self.values.len()
+    }
+
     pub fn is_inside(&self, level: &Level) -> bool {
-        self.x < level.width && self.y < level.height
+        self.values.len() == level.dims.len()
+            && self.values.iter().zip(level.dims.iter()).all(|(&v, d)| v < d.size)
+    }
+
+    // every combination of -1, 0, +1 across `ndims` axes, excluding the all-zero offset;
+    // a 1-changed-axis subset of these is the von Neumann neighbourhood, the full set is Moore's
+    fn unit_offsets(ndims: usize) -> Vec<Vec<i32>> {
+        let total = 3usize.pow(ndims as u32);
+        let mut offsets = Vec::with_capacity(total - 1);
+        for i in 0..total {
+            let mut n = i;
+            let mut offset = Vec::with_capacity(ndims);
+            for _ in 0..ndims {
+                offset.push((n % 3) as i32 - 1);
+                n /= 3;
+            }
+            if offset.iter().any(|&d| d != 0) {
+                offsets.push(offset);
+            }
+        }
+        offsets
     }
 }
 
 impl From<(usize, usize)> for Coord {
     fn from(pair: (usize, usize)) -> Coord {
-        Coord {x: pair.0, y: pair.1}
+        Coord::xy(pair.0, pair.1)
     }
 }
 
 pub enum CoordError {
     TooFew,
-    TooMany,
     ParseIntError
 }
 
@@ -39,256 +79,657 @@ impl From<ParseIntError> for CoordError {
 
 impl FromStr for Coord {
     type Err = CoordError;
-    
+
     fn from_str(s: &str) -> Result<Coord, Self::Err> {
         let chunks = s.split(":").collect::<Vec<&str>>();
-        match chunks.len() {
-            len if len <= 1 =>
-                Err(CoordError::TooFew),
-            len if len >= 3 =>
-                Err(CoordError::TooMany),
-            _ =>
-                Ok(Coord {x: chunks[0].parse()?,
-                          y: chunks[1].parse()?})
+        if chunks.len() < 2 {
+            return Err(CoordError::TooFew)
+        }
+        let mut values = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            values.push(chunk.parse()?);
         }
+        Ok(Coord::new(values))
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
+pub enum LevelError {
+    Empty,
+    SizeMismatch,
+    InvalidElevation
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Land {
-    Pass,
+    Pass(u8),
     Block
 }
 
+// 'a'..='z' covers 26 elevations; this is the highest one `marker()` can render as a letter
+const MAX_ELEVATION: u8 = 25;
+
 impl Land {
+    // clamped so a Land::Pass constructed directly (it's a public tuple variant) with an
+    // out-of-range elevation degrades to 'z' instead of overflowing b'a' + elevation
     pub fn marker(&self) -> char {
         match self {
             Land::Block => '#',
-            Land::Pass => '.'
+            Land::Pass(elevation) => (b'a' + elevation.min(&MAX_ELEVATION)) as char
         }
     }
+
+    pub fn elevation(&self) -> u8 {
+        match self {
+            Land::Block => 0,
+            Land::Pass(elevation) => *elevation
+        }
+    }
+
+    pub fn is_passable(&self) -> bool {
+        match self {
+            Land::Block => false,
+            Land::Pass(_) => true
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Movement {
+    FourWay,
+    EightWay
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Heuristic {
+    Manhattan,
+    Chebyshev,
+    Octile,
+    Euclidean
+}
+
+impl Heuristic {
+    // an admissible default for the given movement: Manhattan never overestimates
+    // orthogonal-only steps, octile never overestimates diagonal steps.
+    pub fn default_for(movement: Movement) -> Heuristic {
+        match movement {
+            Movement::FourWay => Heuristic::Manhattan,
+            Movement::EightWay => Heuristic::Octile
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct SearchConfig {
+    pub max_climb: u8,
+    pub movement: Movement,
+    pub heuristic: Heuristic,
+    pub weight: f32
+}
+
+// one axis of a Level's flat, row-major backing store: `size` is the axis' extent and `offset`
+// is the stride, i.e. how many cells to skip in the flat grid to move by one along this axis
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Dimension {
+    size: usize,
+    offset: usize
+}
+
+impl Dimension {
+    fn build(sizes: &[usize]) -> Vec<Dimension> {
+        let mut dims = Vec::with_capacity(sizes.len());
+        let mut offset = 1;
+        for &size in sizes {
+            dims.push(Dimension {size, offset});
+            offset *= size;
+        }
+        dims
+    }
 }
 
 pub struct Level {
-    grid: Vec<Vec<Land>>,
-    height: usize,
-    width: usize
+    grid: Vec<Land>,
+    dims: Vec<Dimension>,
+    start: Option<Coord>,
+    end: Option<Coord>
 }
 
-impl fmt::Display for Level {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "\n")?;
-        for row in &self.grid {
-            for l in row {
-                write!(f, "{}", l.marker())?;
+impl Level {
+    fn render<F: FnMut(usize) -> bool>(&self, f: &mut fmt::Formatter, marked: &mut F) -> fmt::Result {
+        let width = self.dims[0].size;
+        let height = self.dims.get(1).map(|d| d.size).unwrap_or(1);
+        writeln!(f)?;
+        for (index, land) in self.grid.iter().enumerate() {
+            let x = index % width;
+            let y = (index / width) % height;
+            if x == 0 && y == 0 && index != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", if marked(index) { 'o' } else { land.marker() })?;
+            if x == width - 1 {
+                writeln!(f)?;
             }
-            write!(f, "\n")?;
         }
         Ok(())
     }
 }
 
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.render(f, &mut |_| false)
+    }
+}
+
 
 // only use this on checked coords which are inside
-impl<'a> Index<&'a Coord> for Level {
+impl Index<&Coord> for Level {
     type Output = Land;
 
     fn index(&self, coord: &Coord) -> &Self::Output {
-        &self.grid[coord.y][coord.x]
+        &self.grid[self.flat_index(coord)]
     }
 }
 
 
 impl Level {
 
+    // general N-dimensional constructor: `sizes[i]` is the extent of axis `i`, `grid` is the
+    // row-major flattening of the level (axis 0 fastest-changing)
+    pub fn new(sizes: Vec<usize>, grid: Vec<Land>) -> Result<Self, LevelError> {
+        if sizes.is_empty() || sizes.contains(&0) {
+            return Err(LevelError::Empty)
+        }
+        let expected: usize = sizes.iter().product();
+        if grid.len() != expected {
+            return Err(LevelError::SizeMismatch)
+        }
+        if grid.iter().any(|land| land.elevation() > MAX_ELEVATION) {
+            return Err(LevelError::InvalidElevation)
+        }
+        Ok(Level {grid, dims: Dimension::build(&sizes), start: None, end: None})
+    }
+
+    // 2D convenience constructor for the no-fs path
+    pub fn from_grid(rows: Vec<Vec<Land>>) -> Result<Self, LevelError> {
+        let height = rows.len();
+        if height == 0 {
+            return Err(LevelError::Empty)
+        }
+        let width = rows[0].len();
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(LevelError::SizeMismatch)
+        }
+        let mut grid = Vec::with_capacity(width * height);
+        for row in rows {
+            grid.extend(row);
+        }
+        Level::new(vec![width, height], grid)
+    }
+
+    // loads the `.`/`#`/`a`-`z`/`S`/`E` text format. A single block of lines is a 2D level;
+    // successive blocks separated by a blank line stack into a 3rd (depth) dimension.
+    #[cfg(feature = "std")]
     pub fn from_file(filename: &str) -> Result<Self, ArgsError> {
         let level_txt = fs::read_to_string(filename)?;
         let lines = level_txt.lines().map(String::from).collect::<Vec<_>>();
-        let height = lines.len();
-            
+        let slabs = lines.split(|line| line.is_empty())
+            .filter(|slab| !slab.is_empty())
+            .collect::<Vec<_>>();
+
+        if slabs.is_empty() {
+            return Err(ArgsError::InvalidLevel)
+        }
+
+        let depth = slabs.len();
+        let height = slabs[0].len();
         if height == 0 {
             return Err(ArgsError::InvalidLevel)
         }
-        
-        let width = lines[0].len();
-        let mut rows = Vec::with_capacity(lines.len());
-        
-        for line in lines {
-            if line.len() != width {
+        let width = slabs[0][0].len();
+        if width == 0 {
+            return Err(ArgsError::InvalidLevel)
+        }
+
+        let mut grid = Vec::with_capacity(width * height * depth);
+        let mut start = None;
+        let mut end = None;
+
+        for (z, slab) in slabs.into_iter().enumerate() {
+            if slab.len() != height {
                 return Err(ArgsError::InvalidLevel)
             }
-            let mut row = Vec::with_capacity(width);
-            for c in line.chars() {
-                match c {
-                    '.' => row.push(Land::Pass),
-                    '#' => row.push(Land::Block),
-                    _ => return Err(ArgsError::InvalidLevel)
+            for (y, line) in slab.iter().enumerate() {
+                if line.len() != width {
+                    return Err(ArgsError::InvalidLevel)
+                }
+                for (x, c) in line.chars().enumerate() {
+                    let values = if depth > 1 { vec![x, y, z] } else { vec![x, y] };
+                    grid.push(match c {
+                        '.' => Land::Pass(0),
+                        '#' => Land::Block,
+                        'S' => { start = Some(Coord::new(values)); Land::Pass(0) }
+                        'E' => { end = Some(Coord::new(values)); Land::Pass(25) }
+                        'a'..='z' => Land::Pass(c as u8 - b'a'),
+                        _ => return Err(ArgsError::InvalidLevel)
+                    });
                 }
             }
-            rows.push(row);
         }
-        Ok(Level {grid: rows, height: height, width: width})
+
+        let sizes = if depth > 1 { vec![width, height, depth] } else { vec![width, height] };
+        let mut level = Level::new(sizes, grid).map_err(|_| ArgsError::InvalidLevel)?;
+        level.start = start;
+        level.end = end;
+        Ok(level)
     }
 
-    pub fn dimensions(&self) -> (usize, usize) {
-        (self.width, self.height)
+    pub fn dimensions(&self) -> Vec<usize> {
+        self.dims.iter().map(|d| d.size).collect()
     }
 
-    pub fn max_x(&self) -> usize {
-        self.width - 1
+    pub fn start(&self) -> Option<Coord> {
+        self.start.clone()
     }
 
-    pub fn max_y(&self) -> usize {
-        self.height - 1
+    pub fn end(&self) -> Option<Coord> {
+        self.end.clone()
     }
 
-    fn neighbours(&self, pos: &Coord) -> Vec<Coord> {
-        let min_x = if pos.x == 0 { 0 } else { pos.x - 1 };
-        let min_y = if pos.y == 0 { 0 } else { pos.y - 1 };
-        let max_x = if pos.x == self.max_x() { pos.x } else { pos.x + 1 };
-        let max_y = if pos.y == self.max_y() { pos.y } else { pos.y + 1 };
-        let mut coords = Vec::with_capacity(8);
-        for x in min_x ..= max_x {
-            for y in min_y ..= max_y {
-                let coord = Coord {x, y};
-                if coord != *pos && self[&coord] == Land::Pass {
-                    coords.push(coord);
+    fn flat_index(&self, coord: &Coord) -> usize {
+        debug_assert_eq!(coord.values.len(), self.dims.len(), "coord dimensionality must match the level's");
+        coord.values.iter().zip(self.dims.iter()).map(|(&v, d)| v * d.offset).sum()
+    }
+
+    fn neighbours(&self, pos: &Coord, config: &SearchConfig) -> Vec<Coord> {
+        let max_reachable = self[pos].elevation().saturating_add(config.max_climb);
+        let mut coords = Vec::new();
+
+        for offset in Coord::unit_offsets(pos.values.len()) {
+            let changed_axes = offset.iter().filter(|&&d| d != 0).count();
+            if config.movement == Movement::FourWay && changed_axes != 1 {
+                continue
+            }
+
+            let mut values = Vec::with_capacity(offset.len());
+            let mut inside = true;
+            for (axis, &d) in offset.iter().enumerate() {
+                let v = pos.values[axis] as i64 + d as i64;
+                if v < 0 || v as usize >= self.dims[axis].size {
+                    inside = false;
+                    break
                 }
+                values.push(v as usize);
+            }
+            if !inside {
+                continue
+            }
+
+            let coord = Coord::new(values);
+            if self[&coord].is_passable() && self[&coord].elevation() <= max_reachable {
+                coords.push(coord);
             }
         }
+
         coords
     }
-    
+
 }
 
-fn distance(from: &Coord, to: &Coord) -> f32 {
-    let xs = (from.x as f32 - to.x as f32).powf(2.0);
-    let ys = (from.y as f32 - to.y as f32).powf(2.0);
-    (xs + ys).sqrt()
+// core has no floating-point transcendentals (they live behind std); fall back to libm for them
+// under no_std so the heuristics above work identically either way
+#[cfg(feature = "std")]
+fn sqrt(x: f32) -> f32 { x.sqrt() }
+#[cfg(not(feature = "std"))]
+fn sqrt(x: f32) -> f32 { libm::sqrtf(x) }
+
+#[cfg(feature = "std")]
+fn powf(x: f32, y: f32) -> f32 { x.powf(y) }
+#[cfg(not(feature = "std"))]
+fn powf(x: f32, y: f32) -> f32 { libm::powf(x, y) }
+
+fn distance(heuristic: Heuristic, from: &Coord, to: &Coord) -> f32 {
+    let diffs = from.values.iter().zip(to.values.iter())
+        .map(|(&a, &b)| (a as f32 - b as f32).abs())
+        .collect::<Vec<_>>();
+    match heuristic {
+        Heuristic::Manhattan => diffs.iter().sum::<f32>(),
+        Heuristic::Chebyshev => diffs.iter().cloned().fold(0.0f32, f32::max),
+        Heuristic::Octile => {
+            // n-D diagonal distance: consume the largest diffs first, each extra simultaneous
+            // diagonal step costing sqrt(k) - sqrt(k-1); reduces to max + (sqrt(2)-1)*min at n=2
+            let mut sorted = diffs.clone();
+            sorted.sort_by(|a, b| b.partial_cmp(a).unwrap());
+            let mut cost = 0.0f32;
+            let mut prev_sqrt_k = 0.0f32;
+            for (k, d) in sorted.iter().enumerate() {
+                let sqrt_k = sqrt((k + 1) as f32);
+                cost += (sqrt_k - prev_sqrt_k) * d;
+                prev_sqrt_k = sqrt_k;
+            }
+            cost
+        }
+        Heuristic::Euclidean => sqrt(diffs.iter().map(|d| powf(*d, 2.0)).sum::<f32>())
+    }
+}
+
+// a step changing k axes by one each has true Euclidean length sqrt(k); this generalizes the
+// old fixed REGULAR_COST/DIAGONAL_COST pair (k=1 and k=2) to any number of dimensions
+fn step_cost(from: &Coord, to: &Coord) -> f32 {
+    let changed_axes = from.values.iter().zip(to.values.iter()).filter(|(a, b)| a != b).count();
+    sqrt(changed_axes as f32)
 }
 
 
 pub struct Path<'a> {
     level: &'a Level,
-    coords: Vec<(usize, usize)>,
+    coords: Vec<Coord>,
     distance: f32
 }
 
 impl<'a> fmt::Display for Path<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut groups = HashMap::<usize, Vec<usize>>::new();
-        for (x, y) in self.coords.iter() {
-            groups.entry(*y).and_modify(|xs| xs.push(*x)).or_insert_with(|| vec![*x]);
-        }
-        write!(f, "Path of {:?} coords travels distance of {} units.\n",
-               self.coords.len(), self.distance)?;
-        for (y, row) in self.level.grid.iter().enumerate() {
-            let empty = &vec![];
-            let xs = HashSet::<&usize>::from_iter(groups.get(&y).unwrap_or(empty));
-            for (x, l) in row.iter().enumerate() {
-                write!(f, "{}", if xs.contains(&x) { 'o' } else { l.marker() })?;
-            }
-            write!(f, "\n")?;
-        }
-        Ok(())
+        let marked = HashSet::<usize>::from_iter(self.coords.iter().map(|c| self.level.flat_index(c)));
+        writeln!(f, "Path of {:?} coords travels distance of {} units.",
+                 self.coords.len(), self.distance)?;
+        self.level.render(f, &mut |index| marked.contains(&index))
     }
 }
 
 
-#[derive(Debug, PartialEq)]
-struct Candidate {
-    cost: f32,     // heuristics cost (distance fn)
-    coord: Coord
+// Indexed min-heap over (f32 priority, Coord) keyed by f-cost, supporting change_priority in
+// O(log n) so a cheaper path to an already-open node updates its slot instead of leaving a
+// stale duplicate behind for the A* loop to pop.
+struct OpenSet {
+    heap: Vec<(f32, Coord)>,
+    positions: HashMap<Coord, usize>
 }
 
-// since BinaryHeap is max-heap, we need results reversed (Less -> Greater and vice versa)
-impl PartialOrd for Candidate {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        if self.cost.is_finite() && other.cost.is_finite() {
-            if self.cost < other.cost { return Some(Ordering::Greater) } //Some(Ordering::Less) }
-            if self.cost > other.cost { return Some(Ordering::Less) } //Some(Ordering::Greater) }
-            Some(Ordering::Equal)
-        } else {
-            None
-        }
+impl OpenSet {
+    fn new() -> Self {
+        OpenSet {heap: Vec::new(), positions: HashMap::new()}
     }
-}
 
-impl Ord for Candidate {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap() // f32 come from distance fn which doesn't do division
+    fn contains(&self, coord: &Coord) -> bool {
+        self.positions.contains_key(coord)
     }
-}
 
-impl Eq for Candidate {}
+    fn push(&mut self, cost: f32, coord: Coord) {
+        let index = self.heap.len();
+        self.positions.insert(coord.clone(), index);
+        self.heap.push((cost, coord));
+        self.sift_up(index);
+    }
 
+    fn pop_min(&mut self) -> Option<(f32, Coord)> {
+        if self.heap.is_empty() {
+            return None
+        }
+        let last = self.heap.len() - 1;
+        self.swap(0, last);
+        let (cost, coord) = self.heap.pop().unwrap();
+        self.positions.remove(&coord);
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+        Some((cost, coord))
+    }
 
-const DIAGONAL_COST: f32 = 1.414;
-const REGULAR_COST: f32 = 1.0;    
+    fn change_priority(&mut self, coord: Coord, new_cost: f32) {
+        let index = *self.positions.get(&coord).unwrap(); // only called for coords known to be open
+        let old_cost = self.heap[index].0;
+        self.heap[index].0 = new_cost;
+        if new_cost < old_cost {
+            self.sift_up(index);
+        } else if new_cost > old_cost {
+            self.sift_down(index);
+        }
+    }
 
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        self.positions.insert(self.heap[i].1.clone(), i);
+        self.positions.insert(self.heap[j].1.clone(), j);
+    }
 
-pub fn find(level: &Level, start: Coord, end: Coord) -> Option<Path> {
-    if level[&start] == Land::Block || level[&end] == Land::Block {
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if self.heap[index].0 < self.heap[parent].0 {
+                self.swap(index, parent);
+                index = parent;
+            } else {
+                break
+            }
+        }
+    }
+
+    fn sift_down(&mut self, mut index: usize) {
+        let len = self.heap.len();
+        loop {
+            let left = 2 * index + 1;
+            let right = 2 * index + 2;
+            let mut smallest = index;
+            if left < len && self.heap[left].0 < self.heap[smallest].0 {
+                smallest = left;
+            }
+            if right < len && self.heap[right].0 < self.heap[smallest].0 {
+                smallest = right;
+            }
+            if smallest == index {
+                break
+            }
+            self.swap(index, smallest);
+            index = smallest;
+        }
+    }
+}
+
+pub fn find<'a>(level: &'a Level, start: Coord, end: Coord, config: &SearchConfig) -> Option<Path<'a>> {
+    if !start.is_inside(level) || !end.is_inside(level) {
+        return None
+    }
+    if !level[&start].is_passable() || !level[&end].is_passable() {
         return None
     }
 
-    let init_distance = distance(&start, &end);
-    let init_candidate = Candidate {cost: init_distance, coord: start};
-    let mut candidates = BinaryHeap::<Candidate>::from_iter(vec![init_candidate]);
+    let init_distance = config.weight * distance(config.heuristic, &start, &end);
+    let mut open = OpenSet::new();
+    open.push(init_distance, start.clone());
     let mut origin = HashMap::<Coord, Coord>::new();
-    let mut seen = HashSet::<Coord>::new();
-    let mut open = HashSet::<Coord>::from_iter(vec![start]);
+    let mut closed = HashSet::<Coord>::new();
     let mut prefix_cost = HashMap::<Coord, f32>::from_iter(vec![(start, 0.0)]);
-    let mut whole_cost = HashMap::<Coord, f32>::from_iter(vec![(start, init_distance)]); 
 
     loop {
-        match candidates.pop() {
+        match open.pop_min() {
             None =>
                 return None,
-            Some(Candidate {coord: ref current, cost: distance}) if *current == end => {
-                let mut coords = vec![(current.x, current.y)];
+            Some((cost, current)) if current == end => {
+                let mut coords = vec![current.clone()];
                 let mut cursor = current;
-                while let Some(source_coord) = origin.get(cursor) {
-                    coords.push((source_coord.x, source_coord.y));
-                    cursor = source_coord;
+                while let Some(source_coord) = origin.get(&cursor) {
+                    coords.push(source_coord.clone());
+                    cursor = source_coord.clone();
                 }
-                return Some(Path {level: level, coords: coords, distance: distance})
+                return Some(Path {level, coords, distance: cost})
             }
-            Some(Candidate {coord: current, ..}) => {
+            Some((_, current)) => {
 
-                open.remove(&current);
-                seen.insert(current);
+                closed.insert(current.clone());
 
-                let neighbours = level.neighbours(&current)
+                let neighbours = level.neighbours(&current, config)
                     .into_iter()
-                    .filter(|c| !seen.contains(c))
+                    .filter(|c| !closed.contains(c))
                     .collect::<Vec<_>>();
-                
-                let current_prefix_cost = prefix_cost.get(&current).unwrap().clone();
-                
+
+                let current_prefix_cost = *prefix_cost.get(&current).unwrap();
+
                 for neighbor in neighbours {
-                    let is_diagonal = neighbor.x != current.x && neighbor.y != current.y;
-                    let transition_cost = if is_diagonal { DIAGONAL_COST } else { REGULAR_COST };
+                    let transition_cost = step_cost(&current, &neighbor);
                     let neighbor_prefix_cost = current_prefix_cost + transition_cost;
-                    let neighbor_postfix_cost = distance(&neighbor, &end);
+                    let prev_prefix_cost = prefix_cost.get(&neighbor).cloned().unwrap_or(f32::INFINITY);
+
+                    if neighbor_prefix_cost >= prev_prefix_cost {
+                        continue
+                    }
+
+                    let neighbor_postfix_cost = config.weight * distance(config.heuristic, &neighbor, &end);
                     let neighbor_whole_cost = neighbor_prefix_cost + neighbor_postfix_cost;
-                    
-                    if !open.contains(&neighbor) {
-                        candidates.push(Candidate {cost: neighbor_whole_cost, coord: neighbor});
-                        open.insert(neighbor);
+
+                    if open.contains(&neighbor) {
+                        open.change_priority(neighbor.clone(), neighbor_whole_cost);
                     } else {
-                        let prev_npc = prefix_cost.get(&neighbor).unwrap_or(&INFINITY).clone();
-                        if !(neighbor_prefix_cost < prev_npc) {
-                            continue
-                        }
+                        open.push(neighbor_whole_cost, neighbor.clone());
                     }
-                    origin.insert(neighbor, current);
+
+                    origin.insert(neighbor.clone(), current.clone());
                     prefix_cost.insert(neighbor, neighbor_prefix_cost);
-                    whole_cost.insert(neighbor, neighbor_whole_cost);
                 }
             }
         }
     }
 }
 
+#[cfg(test)]
+mod open_set_tests {
+    use super::*;
+
+    #[test]
+    fn pops_in_ascending_cost_order() {
+        let mut open = OpenSet::new();
+        open.push(5.0, Coord::xy(0, 0));
+        open.push(1.0, Coord::xy(1, 0));
+        open.push(3.0, Coord::xy(2, 0));
+        open.push(2.0, Coord::xy(3, 0));
+
+        let mut popped = Vec::new();
+        while let Some((cost, _)) = open.pop_min() {
+            popped.push(cost);
+        }
+        assert_eq!(popped, vec![1.0, 2.0, 3.0, 5.0]);
+    }
+
+    #[test]
+    fn pop_min_on_empty_returns_none() {
+        let mut open = OpenSet::new();
+        assert!(open.pop_min().is_none());
+    }
+
+    #[test]
+    fn change_priority_reorders_a_lowered_cost_ahead() {
+        let mut open = OpenSet::new();
+        open.push(5.0, Coord::xy(0, 0));
+        open.push(10.0, Coord::xy(1, 0));
+        open.change_priority(Coord::xy(1, 0), 1.0);
+
+        assert_eq!(open.pop_min(), Some((1.0, Coord::xy(1, 0))));
+        assert_eq!(open.pop_min(), Some((5.0, Coord::xy(0, 0))));
+    }
+
+    #[test]
+    fn change_priority_reorders_a_raised_cost_behind() {
+        let mut open = OpenSet::new();
+        open.push(1.0, Coord::xy(0, 0));
+        open.push(2.0, Coord::xy(1, 0));
+        open.change_priority(Coord::xy(0, 0), 10.0);
+
+        assert_eq!(open.pop_min(), Some((2.0, Coord::xy(1, 0))));
+        assert_eq!(open.pop_min(), Some((10.0, Coord::xy(0, 0))));
+    }
+
+    #[test]
+    fn contains_reflects_pushes_and_pops() {
+        let mut open = OpenSet::new();
+        let coord = Coord::xy(4, 4);
+        assert!(!open.contains(&coord));
+        open.push(1.0, coord.clone());
+        assert!(open.contains(&coord));
+        open.pop_min();
+        assert!(!open.contains(&coord));
+    }
+}
+
+#[cfg(test)]
+mod heuristic_tests {
+    use super::*;
+
+    fn assert_approx(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-5, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn manhattan_sums_axis_diffs() {
+        let d = distance(Heuristic::Manhattan, &Coord::xy(0, 0), &Coord::xy(3, 4));
+        assert_approx(d, 7.0);
+    }
+
+    #[test]
+    fn chebyshev_takes_the_largest_axis_diff() {
+        let d = distance(Heuristic::Chebyshev, &Coord::xy(0, 0), &Coord::xy(3, 4));
+        assert_approx(d, 4.0);
+    }
 
+    #[test]
+    fn euclidean_is_straight_line_distance() {
+        let d = distance(Heuristic::Euclidean, &Coord::xy(0, 0), &Coord::xy(3, 4));
+        assert_approx(d, 5.0);
+    }
+
+    #[test]
+    fn octile_matches_the_2d_formula() {
+        // max + (sqrt(2) - 1) * min, the textbook octile distance
+        let d = distance(Heuristic::Octile, &Coord::xy(0, 0), &Coord::xy(4, 3));
+        assert_approx(d, 4.0 + (2.0f32.sqrt() - 1.0) * 3.0);
+    }
+
+    #[test]
+    fn octile_on_an_equal_3d_diagonal_equals_true_euclidean_length() {
+        // a step that changes all 3 axes by the same amount is a true diagonal: its octile
+        // cost should equal sqrt(3), not collapse to the Chebyshev value of 1 (the bug a
+        // global-min/max generalization introduces whenever one axis diff hits zero elsewhere)
+        let d = distance(Heuristic::Octile, &Coord::new(vec![0, 0, 0]), &Coord::new(vec![1, 1, 1]));
+        assert_approx(d, 3.0f32.sqrt());
+    }
+
+    #[test]
+    fn octile_on_an_uneven_3d_diff_tiers_each_axis_by_rank() {
+        // diffs (3, 2, 0): largest axis costs 1 per unit, the next-largest gets the cheaper
+        // diagonal rate once it's moving alongside the first, the zero diff contributes nothing
+        let d = distance(Heuristic::Octile, &Coord::new(vec![0, 0, 0]), &Coord::new(vec![3, 2, 0]));
+        let expected = 3.0 + (2.0f32.sqrt() - 1.0) * 2.0;
+        assert_approx(d, expected);
+    }
+
+    #[test]
+    fn step_cost_is_sqrt_of_changed_axis_count() {
+        assert_approx(step_cost(&Coord::xy(0, 0), &Coord::xy(1, 0)), 1.0);
+        assert_approx(step_cost(&Coord::xy(0, 0), &Coord::xy(1, 1)), 2.0f32.sqrt());
+        assert_approx(step_cost(&Coord::new(vec![0, 0, 0]), &Coord::new(vec![1, 1, 1])), 3.0f32.sqrt());
+    }
+}
+
+#[cfg(test)]
+mod land_tests {
+    use super::*;
+
+    #[test]
+    fn marker_clamps_out_of_range_elevation_instead_of_overflowing() {
+        assert_eq!(Land::Pass(10).marker(), 'k');
+        assert_eq!(Land::Pass(200).marker(), 'z');
+    }
+
+    #[test]
+    fn level_new_rejects_out_of_range_elevation() {
+        match Level::new(vec![1, 1], vec![Land::Pass(200)]) {
+            Err(LevelError::InvalidElevation) => {}
+            other => panic!("expected InvalidElevation, got {:?}", other.map(|_| ()))
+        }
+    }
+
+    #[test]
+    fn from_grid_rejects_out_of_range_elevation() {
+        match Level::from_grid(vec![vec![Land::Pass(200)]]) {
+            Err(LevelError::InvalidElevation) => {}
+            other => panic!("expected InvalidElevation, got {:?}", other.map(|_| ()))
+        }
+    }
+}
@@ -2,12 +2,13 @@ use clap;
 
 use std::{fmt, io};
 
-use app::{Coord, CoordError, Level};
+use app::{Coord, CoordError, Heuristic, Level, Movement, SearchConfig};
 
 pub struct Args {
     pub level: Level,
     pub start: Coord,
-    pub end: Coord
+    pub end: Coord,
+    pub config: SearchConfig
 }
 
 impl fmt::Debug for Args {
@@ -29,6 +30,17 @@ mod arg {
         Coord::from_str(&s).and(Ok(())).or(Err(s.to_string()))
     }
 
+    fn is_u8_valid(s: String) -> Result<(), String> {
+        s.parse::<u8>().and(Ok(())).or(Err(s.to_string()))
+    }
+
+    fn is_weight_valid(s: String) -> Result<(), String> {
+        match s.parse::<f32>() {
+            Ok(weight) if weight.is_finite() && weight > 0.0 => Ok(()),
+            _ => Err(s.to_string())
+        }
+    }
+
     pub fn level<'a, 'b>() -> clap::Arg<'a, 'b> {
         clap::Arg::with_name("level")
             .required(true)
@@ -40,23 +52,66 @@ mod arg {
 
     pub fn start<'a, 'b>() -> clap::Arg<'a, 'b> {
         clap::Arg::with_name("start")
-            .required(true)
+            .required(false)
             .takes_value(true)
             .short("s")
             .long("start")
-            .help("X:Y of start position")
+            .help("X:Y of start position, optional when the level marks one with 'S'")
             .validator(is_coord_valid)
     }
 
     pub fn end<'a, 'b>() -> clap::Arg<'a, 'b> {
         clap::Arg::with_name("end")
-            .required(true)
+            .required(false)
             .takes_value(true)
             .short("e")
             .long("end")
-            .help("X:Y of end position")
+            .help("X:Y of end position, optional when the level marks one with 'E'")
             .validator(is_coord_valid)
     }
+
+    pub fn max_climb<'a, 'b>() -> clap::Arg<'a, 'b> {
+        clap::Arg::with_name("max-climb")
+            .required(false)
+            .takes_value(true)
+            .short("c")
+            .long("max-climb")
+            .help("maximum elevation gain allowed in a single step")
+            .default_value("1")
+            .validator(is_u8_valid)
+    }
+
+    pub fn movement<'a, 'b>() -> clap::Arg<'a, 'b> {
+        clap::Arg::with_name("movement")
+            .required(false)
+            .takes_value(true)
+            .short("m")
+            .long("movement")
+            .help("orthogonal-only (four) or also diagonal (eight) neighbour connectivity")
+            .possible_values(&["four", "eight"])
+            .default_value("eight")
+    }
+
+    pub fn heuristic<'a, 'b>() -> clap::Arg<'a, 'b> {
+        clap::Arg::with_name("heuristic")
+            .required(false)
+            .takes_value(true)
+            .short("u")
+            .long("heuristic")
+            .help("distance heuristic; defaults to one admissible for --movement")
+            .possible_values(&["manhattan", "chebyshev", "octile", "euclidean"])
+    }
+
+    pub fn weight<'a, 'b>() -> clap::Arg<'a, 'b> {
+        clap::Arg::with_name("weight")
+            .required(false)
+            .takes_value(true)
+            .short("w")
+            .long("weight")
+            .help("weighted A* factor applied to the heuristic (f = g + weight*h); > 1 trades optimality for speed")
+            .default_value("1.0")
+            .validator(is_weight_valid)
+    }
 }
 
 
@@ -64,7 +119,8 @@ mod arg {
 pub enum ArgsError {
     InvalidLevelFile(io::Error),
     InvalidLevel,
-    Coord
+    Coord,
+    MissingStartOrEnd
 }
 
 impl From<CoordError> for ArgsError {
@@ -88,16 +144,42 @@ pub fn parse() -> Result<Args, ArgsError> {
         .arg(arg::level())
         .arg(arg::start())
         .arg(arg::end())
+        .arg(arg::max_climb())
+        .arg(arg::movement())
+        .arg(arg::heuristic())
+        .arg(arg::weight())
         .get_matches();
-    
+
     let filename = matches.value_of("level").unwrap().to_string();    // we know level is there
     let level = Level::from_file(&filename)?;
-    let start = matches.value_of("start").unwrap().parse::<Coord>()?; // same for start coord
-    let end = matches.value_of("end").unwrap().parse::<Coord>()?;     // same for end coord
+    let max_climb = matches.value_of("max-climb").unwrap().parse::<u8>().unwrap(); // validated above
+    let weight = matches.value_of("weight").unwrap().parse::<f32>().unwrap();      // validated above
+
+    let movement = match matches.value_of("movement").unwrap() { // restricted to possible_values above
+        "four" => Movement::FourWay,
+        _ => Movement::EightWay
+    };
+    let heuristic = match matches.value_of("heuristic") { // restricted to possible_values above
+        Some("manhattan") => Heuristic::Manhattan,
+        Some("chebyshev") => Heuristic::Chebyshev,
+        Some("octile") => Heuristic::Octile,
+        Some("euclidean") => Heuristic::Euclidean,
+        _ => Heuristic::default_for(movement)
+    };
+    let config = SearchConfig {max_climb, movement, heuristic, weight};
+
+    let start = match matches.value_of("start") {
+        Some(s) => s.parse::<Coord>()?,
+        None => level.start().ok_or(ArgsError::MissingStartOrEnd)?
+    };
+    let end = match matches.value_of("end") {
+        Some(s) => s.parse::<Coord>()?,
+        None => level.end().ok_or(ArgsError::MissingStartOrEnd)?
+    };
 
     if !start.is_inside(&level) || !end.is_inside(&level) {
         return Err(ArgsError::Coord)
     }
-    
-    Ok(Args {level: level, start: start, end: end})
+
+    Ok(Args {level, start, end, config})
 }
@@ -0,0 +1,22 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+extern crate core;
+
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+extern crate hashbrown;
+
+#[cfg(not(feature = "std"))]
+extern crate libm;
+
+#[cfg(feature = "std")]
+extern crate clap;
+
+pub mod app;
+
+#[cfg(feature = "std")]
+pub mod args;
@@ -1,17 +1,15 @@
-extern crate clap;
+extern crate astar;
 
-pub mod args;
-pub mod app;
-
-use args::{Args, ArgsError};
+use astar::args::{self, Args, ArgsError};
+use astar::app;
 
 fn main() -> Result<(), ArgsError> {
-    let Args {level, start, end} = args::parse()?;
+    let Args {level, start, end, config} = args::parse()?;
 
-    match app::find(&level, start, end) {
+    match app::find(&level, start, end, &config) {
         Some(ref path) => println!("{}\n", path),
         None => println!("No path exists.")
     }
-    
+
     Ok(())
 }